@@ -0,0 +1,118 @@
+//! Bound inference for the generated `impl`s, mirroring the approach
+//! `serde_derive` itself uses: rather than requiring every type parameter to
+//! satisfy `Serialize`/`Deserialize<'de>`, only the parameters that actually
+//! show up in a non-skipped field's type are constrained.
+
+use std::collections::HashSet;
+
+use syn::visit::{self, Visit};
+use syn::{Data, Field, Ident};
+
+/// Every field reachable from `data`, in declaration order.
+pub(crate) fn fields(data: &Data) -> Vec<&Field> {
+    match data {
+        Data::Struct(data) => data.fields.iter().collect(),
+        Data::Enum(data) => data.variants.iter().flat_map(|v| &v.fields).collect(),
+        Data::Union(data) => data.fields.named.iter().collect(),
+    }
+}
+
+/// Which trait a set of bounds is being computed for — determines which
+/// per-field attributes opt a field out of the walk, since e.g. a
+/// `serialize_with` field still needs its type to implement `Deserialize`.
+#[derive(Clone, Copy)]
+pub(crate) enum Direction {
+    Serialize,
+    Deserialize,
+}
+
+/// The subset of `all_params` that appear in the type of some field in
+/// `fields`, skipping fields that opt out of `direction` entirely or hand it
+/// off to a custom function.
+pub(crate) fn used_type_params<'a>(
+    all_params: &HashSet<Ident>,
+    fields: impl IntoIterator<Item = &'a Field>,
+    direction: Direction,
+) -> HashSet<Ident> {
+    let mut visitor = FindTyParams {
+        all_params,
+        relevant: HashSet::new(),
+    };
+
+    for field in fields {
+        if field_opts_out(field, direction) {
+            continue;
+        }
+
+        visitor.visit_type(&field.ty);
+    }
+
+    visitor.relevant
+}
+
+/// True for fields carrying `#[serde(skip)]`, `#[serde(with = "...")]`, or
+/// the `direction`-specific `#[serde(skip_serializing)]`/`serialize_with` (or
+/// `skip_deserializing`/`deserialize_with`) — none of these require the
+/// field's type to implement the trait being derived for `direction`.
+fn field_opts_out(field: &Field, direction: Direction) -> bool {
+    field.attrs.iter().any(|attr| {
+        if !attr.path().is_ident("serde") {
+            return false;
+        }
+
+        let mut opts_out = false;
+        let _ = attr.parse_nested_meta(|meta| {
+            let directional = match direction {
+                Direction::Serialize => {
+                    meta.path.is_ident("skip_serializing") || meta.path.is_ident("serialize_with")
+                }
+                Direction::Deserialize => {
+                    meta.path.is_ident("skip_deserializing")
+                        || meta.path.is_ident("deserialize_with")
+                }
+            };
+
+            if directional || meta.path.is_ident("skip") || meta.path.is_ident("with") {
+                opts_out = true;
+            }
+
+            // Consume `= "..."` if present so the parse doesn't error out on
+            // the attributes we're not interested in.
+            if meta.input.peek(syn::Token![=]) {
+                let _: syn::Expr = meta.value()?.parse()?;
+            }
+
+            Ok(())
+        });
+        opts_out
+    })
+}
+
+struct FindTyParams<'a> {
+    all_params: &'a HashSet<Ident>,
+    relevant: HashSet<Ident>,
+}
+
+impl<'ast> Visit<'ast> for FindTyParams<'_> {
+    fn visit_path(&mut self, path: &'ast syn::Path) {
+        if let Some(seg) = path.segments.last() {
+            // PhantomData<T> implements Serialize/Deserialize regardless of
+            // whether T does.
+            if seg.ident == "PhantomData" {
+                return;
+            }
+        }
+
+        // `T::Assoc` is a use of `T`: only the first segment can name a type
+        // parameter, everything after it is a path into that type.
+        if path.leading_colon.is_none() {
+            if let Some(first) = path.segments.first() {
+                if self.all_params.contains(&first.ident) {
+                    self.relevant.insert(first.ident.clone());
+                }
+            }
+        }
+
+        visit::visit_path(self, path);
+    }
+}
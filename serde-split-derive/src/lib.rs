@@ -0,0 +1,372 @@
+use std::collections::HashSet;
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use proc_macro_crate::FoundCrate;
+use syn::{
+    Attribute, Data, DeriveInput, GenericParam, Ident, Lifetime, LifetimeParam, Meta,
+    WherePredicate,
+};
+
+mod bound;
+mod rename;
+
+fn find_serde_crate() -> proc_macro2::TokenStream {
+    match proc_macro_crate::crate_name("serde") {
+        Ok(FoundCrate::Itself) => quote::quote!(crate),
+        Ok(FoundCrate::Name(name)) => {
+            let ident = syn::Ident::new(name.as_str(), Span::call_site());
+            quote::quote!(::#ident)
+        }
+        Err(_) => {
+            panic!("serde is a co-dependency of serde-split")
+        }
+    }
+}
+
+fn find_own_crate() -> proc_macro2::TokenStream {
+    match proc_macro_crate::crate_name("serde-split") {
+        Ok(FoundCrate::Itself) => quote::quote!(crate),
+        Ok(FoundCrate::Name(name)) => {
+            let ident = syn::Ident::new(name.as_str(), Span::call_site());
+            quote::quote!(::#ident)
+        }
+        Err(_) => {
+            panic!("serde-split is a co-dependency of its own derive macros")
+        }
+    }
+}
+
+fn filter_attrs(attrs: &mut Vec<Attribute>, is_json: bool) {
+    let replace = if is_json { "json" } else { "bin" };
+
+    let mut current = 0;
+    while current < attrs.len() {
+        if attrs[current].path().is_ident(replace) {
+            match &mut attrs[current].meta {
+                Meta::Path(path) => *path = syn::parse_quote!(serde),
+                Meta::List(list) => list.path = syn::parse_quote!(serde),
+                Meta::NameValue(name_value) => name_value.path = syn::parse_quote!(serde),
+            }
+        } else if !attrs[current].path().is_ident("serde") {
+            attrs.remove(current);
+            continue;
+        }
+
+        current += 1;
+    }
+}
+
+/// The type parameters declared on `generics`, ignoring lifetimes and const
+/// parameters.
+fn all_type_params(generics: &syn::Generics) -> HashSet<Ident> {
+    generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(ty) => Some(ty.ident.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The type parameters that need a `Serialize`/`Deserialize<'de>` bound on
+/// the outer dispatching `impl`: the union of what's used by the `json` copy
+/// and what's used by the `bin` copy, since the outer `impl` has to be valid
+/// for both of the remote impls it may call into.
+fn bound_type_params(
+    all_params: &HashSet<Ident>,
+    json: &DeriveInput,
+    bin: &DeriveInput,
+    direction: bound::Direction,
+) -> Vec<Ident> {
+    let json_used = bound::used_type_params(all_params, bound::fields(&json.data), direction);
+    let bin_used = bound::used_type_params(all_params, bound::fields(&bin.data), direction);
+
+    all_params
+        .iter()
+        .filter(|param| json_used.contains(*param) || bin_used.contains(*param))
+        .cloned()
+        .collect()
+}
+
+/// Reads `#[serde_split(selector = "path::to::fn")]` off the container, if
+/// present. The referenced function must be `fn(bool) -> bool`: it's handed
+/// the serializer/deserializer's `is_human_readable()` value and returns
+/// whether the `json` (`true`) or `bin` (`false`) impl should be used. This
+/// only overrides *what's done with* that flag — e.g. routing through a
+/// thread-local instead of trusting it — not how it's obtained; there's no
+/// way for a `fn(bool) -> bool` to see the serializer/deserializer itself.
+///
+/// Returns `Err` (rather than silently falling back to `is_human_readable`)
+/// if the attribute is present but malformed, same as serde does for its own
+/// container attributes.
+fn find_selector(attrs: &[Attribute]) -> syn::Result<Option<syn::Path>> {
+    for attr in attrs {
+        if !attr.path().is_ident("serde_split") {
+            continue;
+        }
+
+        let mut selector = None;
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("selector") {
+                let lit: syn::LitStr = meta.value()?.parse()?;
+                selector = Some(lit.parse::<syn::Path>()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported serde_split attribute, expected `selector`"))
+            }
+        })?;
+
+        return match selector {
+            Some(selector) => Ok(Some(selector)),
+            None => Err(syn::Error::new_spanned(
+                attr,
+                "expected `#[serde_split(selector = \"path::to::fn\")]`",
+            )),
+        };
+    }
+
+    Ok(None)
+}
+
+fn filter_data(input: &mut DeriveInput, is_json: bool) {
+    let index_rename = !is_json && rename::take_index_rename_all(&mut input.attrs);
+
+    filter_attrs(&mut input.attrs, is_json);
+
+    match &mut input.data {
+        Data::Struct(data) => {
+            data.fields
+                .iter_mut()
+                .for_each(|field| filter_attrs(&mut field.attrs, is_json));
+            if index_rename {
+                rename::apply_index_renames(&mut data.fields);
+            }
+        }
+        Data::Enum(data) => {
+            data.variants.iter_mut().for_each(|variant| {
+                let variant_has_index_rename_all =
+                    !is_json && rename::take_index_rename_all(&mut variant.attrs);
+                let variant_index_rename = index_rename || variant_has_index_rename_all;
+
+                filter_attrs(&mut variant.attrs, is_json);
+                variant
+                    .fields
+                    .iter_mut()
+                    .for_each(|field| filter_attrs(&mut field.attrs, is_json));
+
+                if variant_index_rename {
+                    rename::apply_index_renames(&mut variant.fields);
+                }
+            });
+        }
+        Data::Union(data) => {
+            data.fields.named.iter_mut().for_each(|field| {
+                filter_attrs(&mut field.attrs, is_json);
+            });
+            if index_rename {
+                rename::apply_index_renames_to(&mut data.fields.named);
+            }
+        }
+    }
+}
+
+#[proc_macro_derive(Serialize, attributes(json, bin, serde, serde_split))]
+pub fn derive_serialize(tokens: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(tokens as syn::DeriveInput);
+
+    let ident = input.ident.clone();
+    let selector = match find_selector(&input.attrs) {
+        Ok(selector) => selector,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    let mut json = input.clone();
+    let mut bin = input;
+
+    filter_data(&mut json, true);
+    filter_data(&mut bin, false);
+
+    json.ident = quote::format_ident!("{}JsonImpl", ident);
+    bin.ident = quote::format_ident!("{}BinaryImpl", ident);
+
+    let json_ident = &json.ident;
+    let bin_ident = &bin.ident;
+
+    let ident_str = syn::LitStr::new(ident.to_string().as_str(), ident.span());
+
+    let serde = find_serde_crate();
+    let split = find_own_crate();
+
+    let (impl_gen, ty_gen, where_clause) = bin.generics.split_for_impl();
+
+    let all_params = all_type_params(&bin.generics);
+    let bound_params = bound_type_params(&all_params, &json, &bin, bound::Direction::Serialize);
+
+    let where_clause = if let Some(clause) = where_clause {
+        let mut clause = clause.clone();
+        clause.predicates.extend(
+            bound_params
+                .iter()
+                .map::<WherePredicate, _>(|ident| syn::parse_quote!(#ident: #serde::Serialize)),
+        );
+        Some(clause)
+    } else if !bound_params.is_empty() {
+        let clauses = bound_params
+            .iter()
+            .map::<WherePredicate, _>(|ident| syn::parse_quote!(#ident: #serde::Serialize));
+
+        Some(syn::parse_quote!(where #(#clauses,)*))
+    } else {
+        None
+    };
+
+    let use_json = match &selector {
+        Some(selector) => quote::quote!(#selector(serializer.is_human_readable())),
+        None => quote::quote!(serializer.is_human_readable()),
+    };
+
+    quote::quote! {
+        const _: () = {
+            #[derive(#serde::Serialize)]
+            #[serde(remote = #ident_str)]
+            #json
+
+            #[derive(#serde::Serialize)]
+            #[serde(remote = #ident_str)]
+            #bin
+
+            impl #impl_gen #split::SplitSerialize for #ident #ty_gen #where_clause {
+                fn serialize_json<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                    where S: #serde::Serializer
+                {
+                    #json_ident::serialize(self, serializer)
+                }
+
+                fn serialize_binary<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                    where S: #serde::Serializer
+                {
+                    #bin_ident::serialize(self, serializer)
+                }
+            }
+
+            impl #impl_gen #serde::Serialize for #ident #ty_gen #where_clause {
+                fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                    where S: #serde::Serializer
+                {
+                    if #use_json {
+                        #split::SplitSerialize::serialize_json(self, serializer)
+                    } else {
+                        #split::SplitSerialize::serialize_binary(self, serializer)
+                    }
+                }
+            }
+        };
+    }
+    .into()
+}
+
+#[proc_macro_derive(Deserialize, attributes(json, bin, serde, serde_split))]
+pub fn derive_deserialize(tokens: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(tokens as syn::DeriveInput);
+
+    let ident = input.ident.clone();
+    let selector = match find_selector(&input.attrs) {
+        Ok(selector) => selector,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    let mut json = input.clone();
+    let mut bin = input;
+
+    filter_data(&mut json, true);
+    filter_data(&mut bin, false);
+
+    json.ident = quote::format_ident!("{}JsonImpl", ident);
+    bin.ident = quote::format_ident!("{}BinaryImpl", ident);
+
+    let json_ident = &json.ident;
+    let bin_ident = &bin.ident;
+
+    let ident_str = syn::LitStr::new(ident.to_string().as_str(), ident.span());
+
+    let serde = find_serde_crate();
+    let split = find_own_crate();
+
+    let mut impl_generics = bin.generics.clone();
+
+    impl_generics.params.insert(
+        0,
+        syn::GenericParam::Lifetime(LifetimeParam::new(Lifetime::new("'de", Span::call_site()))),
+    );
+
+    let (_, ty_gen, where_clause) = bin.generics.split_for_impl();
+
+    let (impl_gen, _, _) = impl_generics.split_for_impl();
+
+    let all_params = all_type_params(&bin.generics);
+    let bound_params = bound_type_params(&all_params, &json, &bin, bound::Direction::Deserialize);
+
+    let where_clause = if let Some(clause) = where_clause {
+        let mut clause = clause.clone();
+        clause.predicates.extend(
+            bound_params.iter().map::<WherePredicate, _>(
+                |ident| syn::parse_quote!(#ident: #serde::Deserialize<'de>),
+            ),
+        );
+        Some(clause)
+    } else if !bound_params.is_empty() {
+        let clauses = bound_params
+            .iter()
+            .map::<WherePredicate, _>(|ident| syn::parse_quote!(#ident: #serde::Deserialize<'de>));
+
+        Some(syn::parse_quote!(where #(#clauses,)*))
+    } else {
+        None
+    };
+
+    let use_json = match &selector {
+        Some(selector) => quote::quote!(#selector(deserializer.is_human_readable())),
+        None => quote::quote!(deserializer.is_human_readable()),
+    };
+
+    quote::quote! {
+        const _: () = {
+            #[derive(#serde::Deserialize)]
+            #[serde(remote = #ident_str)]
+            #json
+
+            #[derive(#serde::Deserialize)]
+            #[serde(remote = #ident_str)]
+            #bin
+
+            impl #impl_gen #split::SplitDeserialize<'de> for #ident #ty_gen #where_clause {
+                fn deserialize_json<D>(deserializer: D) -> Result<Self, D::Error>
+                    where D: #serde::Deserializer<'de>
+                {
+                    #json_ident::deserialize(deserializer)
+                }
+
+                fn deserialize_binary<D>(deserializer: D) -> Result<Self, D::Error>
+                    where D: #serde::Deserializer<'de>
+                {
+                    #bin_ident::deserialize(deserializer)
+                }
+            }
+
+            impl #impl_gen #serde::Deserialize<'de> for #ident #ty_gen #where_clause {
+                fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                    where D: #serde::Deserializer<'de>
+                {
+                    if #use_json {
+                        <#ident #ty_gen as #split::SplitDeserialize>::deserialize_json(deserializer)
+                    } else {
+                        <#ident #ty_gen as #split::SplitDeserialize>::deserialize_binary(deserializer)
+                    }
+                }
+            }
+        };
+    }
+    .into()
+}
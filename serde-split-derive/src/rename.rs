@@ -0,0 +1,119 @@
+//! `#[bin(rename_all = "index")]` support: renames a struct/variant's
+//! fields to their sequential index (`"0"`, `"1"`, ...) in the `bin` copy,
+//! so binary formats write short numeric field names instead of the full
+//! ones. The keys are still strings, not integers.
+
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Field, Fields, Meta, Token};
+
+/// Strips the `rename_all = "index"` item out of any `#[bin(...)]` attribute
+/// in `attrs`, returning whether it was found. `serde` has no `"index"`
+/// casing of its own, so this can never be forwarded like the other
+/// `bin(...)` items are; everything else in the same attribute is left
+/// alone.
+pub(crate) fn take_index_rename_all(attrs: &mut Vec<Attribute>) -> bool {
+    let mut found = false;
+
+    attrs.retain_mut(|attr| {
+        if !attr.path().is_ident("bin") {
+            return true;
+        }
+
+        let Meta::List(list) = &attr.meta else {
+            return true;
+        };
+
+        let Ok(items) = list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+        else {
+            return true;
+        };
+
+        let (index, rest): (Vec<_>, Vec<_>) = items.into_iter().partition(is_index_rename_all);
+        if index.is_empty() {
+            return true;
+        }
+
+        found = true;
+        if rest.is_empty() {
+            return false;
+        }
+
+        let rest = Punctuated::<Meta, Token![,]>::from_iter(rest);
+        attr.meta = Meta::List(syn::MetaList {
+            tokens: quote::quote!(#rest),
+            ..list.clone()
+        });
+        true
+    });
+
+    found
+}
+
+fn is_index_rename_all(meta: &Meta) -> bool {
+    let Meta::NameValue(name_value) = meta else {
+        return false;
+    };
+    if !name_value.path.is_ident("rename_all") {
+        return false;
+    }
+    let syn::Expr::Lit(syn::ExprLit {
+        lit: syn::Lit::Str(lit),
+        ..
+    }) = &name_value.value
+    else {
+        return false;
+    };
+    lit.value() == "index"
+}
+
+/// Injects `#[serde(rename = "N")]` onto each non-skipped field in
+/// declaration order, starting at `0`. A field that already carries an
+/// explicit rename keeps it, but still consumes an index so positions stay
+/// stable; a `#[serde(skip)]` field consumes no index at all.
+///
+/// `serde` ignores `rename` on tuple fields, so there's nothing useful to do
+/// for `Fields::Unnamed`/`Fields::Unit` — this is a no-op for those.
+pub(crate) fn apply_index_renames(fields: &mut Fields) {
+    if let Fields::Named(named) = fields {
+        apply_index_renames_to(&mut named.named);
+    }
+}
+
+pub(crate) fn apply_index_renames_to<'a>(fields: impl IntoIterator<Item = &'a mut Field>) {
+    let mut index = 0usize;
+
+    for field in fields {
+        if has_meta(field, "skip") {
+            continue;
+        }
+
+        if !has_meta(field, "rename") {
+            let rename = index.to_string();
+            field
+                .attrs
+                .push(syn::parse_quote!(#[serde(rename = #rename)]));
+        }
+
+        index += 1;
+    }
+}
+
+fn has_meta(field: &Field, name: &str) -> bool {
+    field.attrs.iter().any(|attr| {
+        if !attr.path().is_ident("serde") {
+            return false;
+        }
+
+        let mut found = false;
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident(name) {
+                found = true;
+            }
+            if meta.input.peek(syn::Token![=]) {
+                let _: syn::Expr = meta.value()?.parse()?;
+            }
+            Ok(())
+        });
+        found
+    })
+}
@@ -0,0 +1,121 @@
+//! Hand-written companions to the generated code: a way to force a
+//! particular representation for a field (or any other value) instead of
+//! going through `Serializer::is_human_readable`/`Deserializer::is_human_readable`.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Implemented by every type deriving [`Serialize`](macro@crate::Serialize),
+/// exposing its JSON and binary halves directly so they can be driven by
+/// [`Json`]/[`Binary`] or the [`json`]/[`bin`] modules.
+pub trait SplitSerialize {
+    fn serialize_json<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer;
+
+    fn serialize_binary<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer;
+}
+
+/// Implemented by every type deriving [`Deserialize`](macro@crate::Deserialize),
+/// exposing its JSON and binary halves directly so they can be driven by
+/// [`Json`]/[`Binary`] or the [`json`]/[`bin`] modules.
+pub trait SplitDeserialize<'de>: Sized {
+    fn deserialize_json<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>;
+
+    fn deserialize_binary<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>;
+}
+
+/// Forces `T` through its JSON representation, regardless of what
+/// `is_human_readable()` says on the serializer/deserializer actually in use.
+pub struct Json<T>(pub T);
+
+impl<T: SplitSerialize> Serialize for Json<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize_json(serializer)
+    }
+}
+
+impl<'de, T: SplitDeserialize<'de>> Deserialize<'de> for Json<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize_json(deserializer).map(Json)
+    }
+}
+
+/// Forces `T` through its binary representation, regardless of what
+/// `is_human_readable()` says on the serializer/deserializer actually in use.
+pub struct Binary<T>(pub T);
+
+impl<T: SplitSerialize> Serialize for Binary<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize_binary(serializer)
+    }
+}
+
+impl<'de, T: SplitDeserialize<'de>> Deserialize<'de> for Binary<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize_binary(deserializer).map(Binary)
+    }
+}
+
+/// Free functions for `#[serde(with = "serde_split::json")]`, forcing a
+/// field through its JSON representation.
+pub mod json {
+    use super::{SplitDeserialize, SplitSerialize};
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: SplitSerialize,
+        S: Serializer,
+    {
+        value.serialize_json(serializer)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: SplitDeserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        T::deserialize_json(deserializer)
+    }
+}
+
+/// Free functions for `#[serde(with = "serde_split::bin")]`, forcing a field
+/// through its binary representation.
+pub mod bin {
+    use super::{SplitDeserialize, SplitSerialize};
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: SplitSerialize,
+        S: Serializer,
+    {
+        value.serialize_binary(serializer)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: SplitDeserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        T::deserialize_binary(deserializer)
+    }
+}